@@ -0,0 +1,87 @@
+//! Memory-mapped I/O: instead of addressing one flat 64 KiB array
+//! directly, the CPU talks to a `Bus` that dispatches each access to
+//! whichever device is mapped over that address range. RAM is just the
+//! first such device; timers, a display, or controller ports can be
+//! mapped in the same way later.
+
+use crate::clock::Clock;
+
+type BYTE = u8;
+type WORD = u16;
+
+/// A device that can be mapped into the CPU's address space.
+///
+/// Addresses are passed relative to the start of the device's mapped
+/// region, so a device doesn't need to know where the bus placed it.
+pub trait Addressable {
+    fn read_byte(&mut self, clock: &mut Clock, addr: WORD) -> BYTE;
+    fn write_byte(&mut self, clock: &mut Clock, addr: WORD, value: BYTE);
+}
+
+/// Flat RAM, the simplest possible `Addressable` device.
+pub struct Ram {
+    data: Vec<BYTE>,
+}
+
+impl Ram {
+    pub fn new(size: usize) -> Self {
+        Ram { data: vec![0; size] }
+    }
+}
+
+impl Addressable for Ram {
+    fn read_byte(&mut self, _clock: &mut Clock, addr: WORD) -> BYTE {
+        self.data[addr as usize]
+    }
+
+    fn write_byte(&mut self, _clock: &mut Clock, addr: WORD, value: BYTE) {
+        self.data[addr as usize] = value;
+    }
+}
+
+/// Routes reads and writes to whichever mapped device owns the address.
+/// Regions are kept sorted by start address so lookups are a binary
+/// search rather than a linear scan.
+pub struct Bus {
+    regions: Vec<(WORD, WORD, Box<dyn Addressable>)>,
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Bus { regions: Vec::new() }
+    }
+
+    /// Map `device` over the inclusive address range `[start, end]`.
+    pub fn map(&mut self, start: WORD, end: WORD, device: Box<dyn Addressable>) {
+        let pos = self.regions.partition_point(|(region_start, _, _)| *region_start < start);
+        self.regions.insert(pos, (start, end, device));
+    }
+
+    fn owner(&mut self, addr: WORD) -> Option<(WORD, &mut Box<dyn Addressable>)> {
+        let pos = self.regions.partition_point(|(start, _, _)| *start <= addr);
+        let index = pos.checked_sub(1)?;
+        let (start, end, device) = &mut self.regions[index];
+        (addr <= *end).then_some((*start, device))
+    }
+
+    pub fn read_byte(&mut self, clock: &mut Clock, addr: WORD) -> BYTE {
+        match self.owner(addr) {
+            Some((start, device)) => device.read_byte(clock, addr - start),
+            None => 0,
+        }
+    }
+
+    pub fn write_byte(&mut self, clock: &mut Clock, addr: WORD, value: BYTE) {
+        if let Some((start, device)) = self.owner(addr) {
+            device.write_byte(clock, addr - start, value);
+        }
+    }
+
+    /// Read a byte without charging it to a real clock. For tooling (the
+    /// disassembler, a debugger) that wants to peek at memory without
+    /// disturbing the simulation's notion of elapsed time.
+    pub fn peek_byte(&mut self, addr: WORD) -> BYTE {
+        let mut scratch = Clock::with_frequency_hz(1.0);
+        self.read_byte(&mut scratch, addr)
+    }
+}
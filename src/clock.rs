@@ -0,0 +1,76 @@
+//! Femtosecond-resolution simulation clock.
+//!
+//! The core loop used to count down a raw `u32` cycle budget with
+//! `*cycles -= 1`, which underflows (and panics in debug builds) the
+//! moment a single instruction costs more cycles than remain in the
+//! budget. Tracking an absolute elapsed time instead, and only ever
+//! adding to it, removes the underflow entirely and gives a real notion
+//! of elapsed wall-clock time that devices can synchronize against.
+
+use std::ops::{Add, Mul, Sub};
+
+const FEMTOS_PER_SECOND: u64 = 1_000_000_000_000_000;
+
+/// A duration (or absolute timestamp, relative to a clock's epoch) in
+/// femtoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ClockDuration(pub u64);
+
+impl Add for ClockDuration {
+    type Output = ClockDuration;
+
+    fn add(self, rhs: ClockDuration) -> ClockDuration {
+        ClockDuration(self.0 + rhs.0)
+    }
+}
+
+impl Sub for ClockDuration {
+    type Output = ClockDuration;
+
+    fn sub(self, rhs: ClockDuration) -> ClockDuration {
+        ClockDuration(self.0 - rhs.0)
+    }
+}
+
+impl Mul<u32> for ClockDuration {
+    type Output = ClockDuration;
+
+    fn mul(self, rhs: u32) -> ClockDuration {
+        ClockDuration(self.0 * rhs as u64)
+    }
+}
+
+/// A simulation clock: an absolute elapsed time plus the duration of one
+/// cycle at a configured frequency (e.g. 1.023 MHz for an NTSC 6502).
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    time: ClockDuration,
+    cycle_duration: ClockDuration,
+}
+
+impl Clock {
+    /// Build a clock ticking at `frequency_hz` cycles per second, starting
+    /// at time zero.
+    pub fn with_frequency_hz(frequency_hz: f64) -> Self {
+        let femtos_per_cycle = (FEMTOS_PER_SECOND as f64 / frequency_hz).round() as u64;
+        Clock {
+            time: ClockDuration(0),
+            cycle_duration: ClockDuration(femtos_per_cycle),
+        }
+    }
+
+    /// The clock's current elapsed time.
+    pub fn now(&self) -> ClockDuration {
+        self.time
+    }
+
+    /// How long a single cycle takes at this clock's configured frequency.
+    pub fn cycle_duration(&self) -> ClockDuration {
+        self.cycle_duration
+    }
+
+    /// Advance the clock by `cycles` worth of time.
+    pub fn tick(&mut self, cycles: u32) {
+        self.time = self.time + self.cycle_duration * cycles;
+    }
+}
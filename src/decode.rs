@@ -0,0 +1,271 @@
+//! Instruction decoding for the 6502: splits a raw opcode byte into an
+//! operation and an addressing mode, separate from the execution step in
+//! `main.rs`. Keeping decode and execute apart means a disassembler can
+//! reuse the exact same table instead of re-deriving it by hand.
+
+type BYTE = u8;
+
+/// The operation half of a decoded instruction.
+///
+/// `Unimplemented` carries the raw opcode byte so callers (execution,
+/// disassembly, the fuzz harness) can report *which* opcode is missing
+/// instead of silently falling through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lda, Ldx, Ldy,
+    Sta, Stx, Sty,
+    Adc, Sbc,
+    And, Ora, Eor, Bit,
+    Asl, Lsr, Rol, Ror,
+    Cmp, Cpx, Cpy,
+    Inc, Inx, Iny,
+    Dec, Dex, Dey,
+    Tax, Txa, Tay, Tya, Tsx, Txs,
+    Pha, Pla, Php, Plp,
+    Jmp, Jsr, Rts,
+    Brk, Rti,
+    Bcc, Bcs, Beq, Bmi, Bne, Bpl, Bvc, Bvs,
+    Clc, Cld, Cli, Clv, Sec, Sed, Sei,
+    Nop,
+    Unimplemented(BYTE),
+}
+
+/// The addressing-mode half of a decoded instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddrMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+}
+
+/// Decode a raw opcode byte into its operation, addressing mode and base
+/// cycle cost (before any page-cross or branch-taken penalty).
+///
+/// This match is exhaustive over all 256 opcode values, which is what
+/// gives us the "256-entry table" without a 256-element array literal:
+/// every byte maps to exactly one `(Op, AddrMode, cycles)` triple, and
+/// opcodes the 6502 doesn't define (or that we haven't implemented yet)
+/// decode to `Op::Unimplemented` rather than being absent from the match.
+pub fn decode(opcode: BYTE) -> (Op, AddrMode, u32) {
+    use AddrMode::*;
+    use Op::*;
+
+    match opcode {
+        0xA9 => (Lda, Immediate, 2),
+        0xA5 => (Lda, ZeroPage, 3),
+        0xB5 => (Lda, ZeroPageX, 4),
+        0xAD => (Lda, Absolute, 4),
+        0xBD => (Lda, AbsoluteX, 4),
+        0xB9 => (Lda, AbsoluteY, 4),
+        0xA1 => (Lda, IndirectX, 6),
+        0xB1 => (Lda, IndirectY, 5),
+
+        0xA2 => (Ldx, Immediate, 2),
+        0xA6 => (Ldx, ZeroPage, 3),
+        0xB6 => (Ldx, ZeroPageY, 4),
+        0xAE => (Ldx, Absolute, 4),
+        0xBE => (Ldx, AbsoluteY, 4),
+
+        0xA0 => (Ldy, Immediate, 2),
+        0xA4 => (Ldy, ZeroPage, 3),
+        0xB4 => (Ldy, ZeroPageX, 4),
+        0xAC => (Ldy, Absolute, 4),
+        0xBC => (Ldy, AbsoluteX, 4),
+
+        0x85 => (Sta, ZeroPage, 3),
+        0x95 => (Sta, ZeroPageX, 4),
+        0x8D => (Sta, Absolute, 4),
+        0x9D => (Sta, AbsoluteX, 5),
+        0x99 => (Sta, AbsoluteY, 5),
+        0x81 => (Sta, IndirectX, 6),
+        0x91 => (Sta, IndirectY, 6),
+
+        0x86 => (Stx, ZeroPage, 3),
+        0x96 => (Stx, ZeroPageY, 4),
+        0x8E => (Stx, Absolute, 4),
+
+        0x84 => (Sty, ZeroPage, 3),
+        0x94 => (Sty, ZeroPageX, 4),
+        0x8C => (Sty, Absolute, 4),
+
+        0x69 => (Adc, Immediate, 2),
+        0x65 => (Adc, ZeroPage, 3),
+        0x75 => (Adc, ZeroPageX, 4),
+        0x6D => (Adc, Absolute, 4),
+        0x7D => (Adc, AbsoluteX, 4),
+        0x79 => (Adc, AbsoluteY, 4),
+        0x61 => (Adc, IndirectX, 6),
+        0x71 => (Adc, IndirectY, 5),
+
+        0xE9 => (Sbc, Immediate, 2),
+        0xE5 => (Sbc, ZeroPage, 3),
+        0xF5 => (Sbc, ZeroPageX, 4),
+        0xED => (Sbc, Absolute, 4),
+        0xFD => (Sbc, AbsoluteX, 4),
+        0xF9 => (Sbc, AbsoluteY, 4),
+        0xE1 => (Sbc, IndirectX, 6),
+        0xF1 => (Sbc, IndirectY, 5),
+
+        0x29 => (And, Immediate, 2),
+        0x25 => (And, ZeroPage, 3),
+        0x35 => (And, ZeroPageX, 4),
+        0x2D => (And, Absolute, 4),
+        0x3D => (And, AbsoluteX, 4),
+        0x39 => (And, AbsoluteY, 4),
+        0x21 => (And, IndirectX, 6),
+        0x31 => (And, IndirectY, 5),
+
+        0x09 => (Ora, Immediate, 2),
+        0x05 => (Ora, ZeroPage, 3),
+        0x15 => (Ora, ZeroPageX, 4),
+        0x0D => (Ora, Absolute, 4),
+        0x1D => (Ora, AbsoluteX, 4),
+        0x19 => (Ora, AbsoluteY, 4),
+        0x01 => (Ora, IndirectX, 6),
+        0x11 => (Ora, IndirectY, 5),
+
+        0x49 => (Eor, Immediate, 2),
+        0x45 => (Eor, ZeroPage, 3),
+        0x55 => (Eor, ZeroPageX, 4),
+        0x4D => (Eor, Absolute, 4),
+        0x5D => (Eor, AbsoluteX, 4),
+        0x59 => (Eor, AbsoluteY, 4),
+        0x41 => (Eor, IndirectX, 6),
+        0x51 => (Eor, IndirectY, 5),
+
+        0x24 => (Bit, ZeroPage, 3),
+        0x2C => (Bit, Absolute, 4),
+
+        0x0A => (Asl, Accumulator, 2),
+        0x06 => (Asl, ZeroPage, 5),
+        0x16 => (Asl, ZeroPageX, 6),
+        0x0E => (Asl, Absolute, 6),
+        0x1E => (Asl, AbsoluteX, 7),
+
+        0x4A => (Lsr, Accumulator, 2),
+        0x46 => (Lsr, ZeroPage, 5),
+        0x56 => (Lsr, ZeroPageX, 6),
+        0x4E => (Lsr, Absolute, 6),
+        0x5E => (Lsr, AbsoluteX, 7),
+
+        0x2A => (Rol, Accumulator, 2),
+        0x26 => (Rol, ZeroPage, 5),
+        0x36 => (Rol, ZeroPageX, 6),
+        0x2E => (Rol, Absolute, 6),
+        0x3E => (Rol, AbsoluteX, 7),
+
+        0x6A => (Ror, Accumulator, 2),
+        0x66 => (Ror, ZeroPage, 5),
+        0x76 => (Ror, ZeroPageX, 6),
+        0x6E => (Ror, Absolute, 6),
+        0x7E => (Ror, AbsoluteX, 7),
+
+        0xC9 => (Cmp, Immediate, 2),
+        0xC5 => (Cmp, ZeroPage, 3),
+        0xD5 => (Cmp, ZeroPageX, 4),
+        0xCD => (Cmp, Absolute, 4),
+        0xDD => (Cmp, AbsoluteX, 4),
+        0xD9 => (Cmp, AbsoluteY, 4),
+        0xC1 => (Cmp, IndirectX, 6),
+        0xD1 => (Cmp, IndirectY, 5),
+
+        0xE0 => (Cpx, Immediate, 2),
+        0xE4 => (Cpx, ZeroPage, 3),
+        0xEC => (Cpx, Absolute, 4),
+
+        0xC0 => (Cpy, Immediate, 2),
+        0xC4 => (Cpy, ZeroPage, 3),
+        0xCC => (Cpy, Absolute, 4),
+
+        0xE6 => (Inc, ZeroPage, 5),
+        0xF6 => (Inc, ZeroPageX, 6),
+        0xEE => (Inc, Absolute, 6),
+        0xFE => (Inc, AbsoluteX, 7),
+        0xE8 => (Inx, Implied, 2),
+        0xC8 => (Iny, Implied, 2),
+
+        0xC6 => (Dec, ZeroPage, 5),
+        0xD6 => (Dec, ZeroPageX, 6),
+        0xCE => (Dec, Absolute, 6),
+        0xDE => (Dec, AbsoluteX, 7),
+        0xCA => (Dex, Implied, 2),
+        0x88 => (Dey, Implied, 2),
+
+        0xAA => (Tax, Implied, 2),
+        0x8A => (Txa, Implied, 2),
+        0xA8 => (Tay, Implied, 2),
+        0x98 => (Tya, Implied, 2),
+        0xBA => (Tsx, Implied, 2),
+        0x9A => (Txs, Implied, 2),
+
+        0x48 => (Pha, Implied, 3),
+        0x68 => (Pla, Implied, 4),
+        0x08 => (Php, Implied, 3),
+        0x28 => (Plp, Implied, 4),
+
+        0x4C => (Jmp, Absolute, 3),
+        0x6C => (Jmp, Indirect, 5),
+        0x20 => (Jsr, Absolute, 6),
+        0x60 => (Rts, Implied, 6),
+
+        0x00 => (Brk, Implied, 7),
+        0x40 => (Rti, Implied, 6),
+
+        0x90 => (Bcc, Relative, 2),
+        0xB0 => (Bcs, Relative, 2),
+        0xF0 => (Beq, Relative, 2),
+        0x30 => (Bmi, Relative, 2),
+        0xD0 => (Bne, Relative, 2),
+        0x10 => (Bpl, Relative, 2),
+        0x50 => (Bvc, Relative, 2),
+        0x70 => (Bvs, Relative, 2),
+
+        0x18 => (Clc, Implied, 2),
+        0xD8 => (Cld, Implied, 2),
+        0x58 => (Cli, Implied, 2),
+        0xB8 => (Clv, Implied, 2),
+        0x38 => (Sec, Implied, 2),
+        0xF8 => (Sed, Implied, 2),
+        0x78 => (Sei, Implied, 2),
+
+        0xEA => (Nop, Implied, 2),
+
+        other => (Unimplemented(other), Implied, 0),
+    }
+}
+
+/// The mnemonic text for a decoded operation, for the disassembler.
+/// `Unimplemented` has no real mnemonic; callers print the raw byte
+/// instead of calling this for it.
+pub fn mnemonic(op: Op) -> &'static str {
+    use Op::*;
+
+    match op {
+        Lda => "LDA", Ldx => "LDX", Ldy => "LDY",
+        Sta => "STA", Stx => "STX", Sty => "STY",
+        Adc => "ADC", Sbc => "SBC",
+        And => "AND", Ora => "ORA", Eor => "EOR", Bit => "BIT",
+        Asl => "ASL", Lsr => "LSR", Rol => "ROL", Ror => "ROR",
+        Cmp => "CMP", Cpx => "CPX", Cpy => "CPY",
+        Inc => "INC", Inx => "INX", Iny => "INY",
+        Dec => "DEC", Dex => "DEX", Dey => "DEY",
+        Tax => "TAX", Txa => "TXA", Tay => "TAY", Tya => "TYA", Tsx => "TSX", Txs => "TXS",
+        Pha => "PHA", Pla => "PLA", Php => "PHP", Plp => "PLP",
+        Jmp => "JMP", Jsr => "JSR", Rts => "RTS",
+        Brk => "BRK", Rti => "RTI",
+        Bcc => "BCC", Bcs => "BCS", Beq => "BEQ", Bmi => "BMI", Bne => "BNE", Bpl => "BPL", Bvc => "BVC", Bvs => "BVS",
+        Clc => "CLC", Cld => "CLD", Cli => "CLI", Clv => "CLV", Sec => "SEC", Sed => "SED", Sei => "SEI",
+        Nop => "NOP",
+        Unimplemented(_) => "???",
+    }
+}
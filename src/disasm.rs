@@ -0,0 +1,55 @@
+//! Textual disassembly for debugging, built on the same `decode` table
+//! `exec` uses so the two can never drift apart: whatever `decode` says an
+//! opcode does is exactly what this prints.
+
+use crate::bus::Bus;
+use crate::decode::{decode, mnemonic, AddrMode, Op};
+
+type WORD = u16;
+
+/// Disassemble the instruction at `addr`, returning its mnemonic plus
+/// formatted operand (e.g. `LDA #$42`, `LDA $42,X`, `JSR $1234`,
+/// `BNE $+6`) and the instruction's length in bytes, so a caller can step
+/// to the next instruction without re-decoding this one.
+pub fn disassemble(bus: &mut Bus, addr: WORD) -> (String, u16) {
+    let opcode = bus.peek_byte(addr);
+    let (op, mode, _base_cycles) = decode(opcode);
+
+    if let Op::Unimplemented(raw) = op {
+        return (format!(".byte ${:02X}", raw), 1);
+    }
+
+    let (operand, len) = match mode {
+        AddrMode::Implied => (String::new(), 1),
+        AddrMode::Accumulator => ("A".to_string(), 1),
+        AddrMode::Immediate => (format!("#${:02X}", bus.peek_byte(addr + 1)), 2),
+        AddrMode::ZeroPage => (format!("${:02X}", bus.peek_byte(addr + 1)), 2),
+        AddrMode::ZeroPageX => (format!("${:02X},X", bus.peek_byte(addr + 1)), 2),
+        AddrMode::ZeroPageY => (format!("${:02X},Y", bus.peek_byte(addr + 1)), 2),
+        AddrMode::Absolute => (format!("${:04X}", peek_word(bus, addr + 1)), 3),
+        AddrMode::AbsoluteX => (format!("${:04X},X", peek_word(bus, addr + 1)), 3),
+        AddrMode::AbsoluteY => (format!("${:04X},Y", peek_word(bus, addr + 1)), 3),
+        AddrMode::Indirect => (format!("(${:04X})", peek_word(bus, addr + 1)), 3),
+        AddrMode::IndirectX => (format!("(${:02X},X)", bus.peek_byte(addr + 1)), 2),
+        AddrMode::IndirectY => (format!("(${:02X}),Y", bus.peek_byte(addr + 1)), 2),
+        AddrMode::Relative => {
+            // Shown as the displacement from the branch instruction's own
+            // address, matching how assemblers print relative branches.
+            let offset = bus.peek_byte(addr + 1) as i8 as i32;
+            (format!("${:+}", 2 + offset), 2)
+        }
+    };
+
+    let text = if operand.is_empty() {
+        mnemonic(op).to_string()
+    } else {
+        format!("{} {}", mnemonic(op), operand)
+    };
+    (text, len)
+}
+
+fn peek_word(bus: &mut Bus, addr: WORD) -> u16 {
+    let lo = bus.peek_byte(addr) as u16;
+    let hi = bus.peek_byte(addr + 1) as u16;
+    lo | (hi << 8)
+}
@@ -0,0 +1,18 @@
+//! Bit layout of the 6502 processor status register: `N V 1 B D I Z C`.
+//!
+//! Bit 5 is unused and always reads back as 1. Bit 4 (`B`) has no physical
+//! latch on real hardware — it only reflects whether the byte currently on
+//! the stack was pushed by a software `BRK`/`PHP` or a hardware `IRQ`/`NMI`,
+//! which is why pushing and pulling the status byte go through separate
+//! helpers (`CPU::to_byte`/`CPU::set_flags_from`) instead of a plain copy.
+
+type BYTE = u8;
+
+pub const CARRY: BYTE = 1 << 0;
+pub const ZERO: BYTE = 1 << 1;
+pub const IRQ_DISABLE: BYTE = 1 << 2;
+pub const DECIMAL: BYTE = 1 << 3;
+pub const BREAK: BYTE = 1 << 4;
+pub const UNUSED: BYTE = 1 << 5;
+pub const OVERFLOW: BYTE = 1 << 6;
+pub const NEGATIVE: BYTE = 1 << 7;
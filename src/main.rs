@@ -1,145 +1,976 @@
-use std::ops::{Index, IndexMut};
-use std::str::Bytes;
+mod bus;
+mod clock;
+mod decode;
+mod disasm;
+mod flags;
 
+use bus::{Bus, Ram};
+use clock::Clock;
+use decode::{decode, AddrMode, Op};
 
 type BYTE = u8;
 type WORD = u16;
 
-#[derive(Debug)]
-struct MEM {
-    data: [BYTE; 1024 * 64]
-}
-
-impl MEM {
-    fn write_word(&mut self, data: WORD, addr: u32, cycles: &mut u32) {
-        self[addr as usize] = (data & 0xFF) as u8;
-        self[addr as usize + 1] = (data >> 8) as u8;
-        *cycles -= 2;
-    }
-}
-
-impl Index<usize> for MEM {
-    type Output = BYTE;
+/// How many cycles `reset`/`irq`/`nmi` cost: pushing PC and P (3 cycles,
+/// skipped on reset since the stack writes are suppressed) plus the
+/// two-byte vector fetch, rounded up to the real hardware's well-known
+/// 7-cycle sequence. These aren't decoded instructions, so they aren't
+/// priced by `decode`'s `base_cycles` the way `step`-driven opcodes are.
+const INTERRUPT_SEQUENCE_CYCLES: u32 = 7;
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index]
-    }
-}
-
-impl IndexMut<usize> for MEM {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.data[index]
-    }
+/// The effective operand produced by resolving an addressing mode: either
+/// a memory location to read/write, the accumulator, an immediate value
+/// already fetched from the instruction stream, or nothing (implied).
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Implied,
+    Accumulator,
+    Immediate(BYTE),
+    Address(WORD),
 }
 
 #[derive(Debug)]
 struct CPU {
     // program counter
     pc: WORD,
-    // stack pointer
-    sp: WORD,
+    // stack pointer (8-bit; the stack always lives in page 1, 0x0100-0x01FF)
+    sp: BYTE,
     // registers
-    a: BYTE, 
+    a: BYTE,
     x: BYTE,
     y: BYTE,
-    C: BYTE, // Carry flag
-    Z: bool, // Zero Flag
-    I: BYTE, // IRQ Disable flag
-    D: BYTE, // Decimal mode flag
-    B: BYTE, // Break command flag
-    V: BYTE, // overflow flag
-    N: bool, // negative flag
+    // packed processor status register, see `flags`
+    p: BYTE,
+    // when set, `exec` prints a disassembly line plus register/flag state
+    // before executing each instruction
+    trace: bool,
+    // level-sensitive IRQ line: held asserted by a device until it's
+    // serviced (or the device deasserts it), same as real hardware
+    irq_line: bool,
+    // edge-sensitive NMI line: latched by `signal_nmi` and consumed by
+    // the next `step` regardless of `IRQ_DISABLE`
+    nmi_line: bool,
 }
 
 impl CPU {
     // opcodes
-    const INS_LDA_IM :BYTE = 0xA9;
     const INS_LDA_ZP :BYTE = 0xA5;
 
-    fn reset(&mut self, mem: &mut MEM) {
-        self.pc = 0xFFFC;
-        self.sp = 0x00FF;
-        self.D = 0;
+    fn reset(&mut self, clock: &mut Clock, bus: &mut Bus) {
+        self.sp = 0xFF;
+        self.p = flags::UNUSED | flags::IRQ_DISABLE;
         self.a = 0;
         self.x = 0;
-        self.y = 0
+        self.y = 0;
+        self.pc = self.read_vector(0xFFFC, clock, bus);
+        clock.tick(INTERRUPT_SEQUENCE_CYCLES);
     }
 
-    
+    fn read_vector(&self, addr: WORD, clock: &mut Clock, bus: &mut Bus) -> WORD {
+        let lo = bus.read_byte(clock, addr) as WORD;
+        let hi = bus.read_byte(clock, addr + 1) as WORD;
+        lo | (hi << 8)
+    }
 
-    fn fetch_byte(&mut self, cycles: &mut u32, mem: &mut MEM) -> BYTE {
-        let data = mem[self.pc as usize];
-        self.pc += 1;
-        *cycles -= 1;
-        return data;
+    fn flag(&self, mask: BYTE) -> bool {
+        self.p & mask != 0
     }
 
-    fn fetch_word(&mut self, cycles: &mut u32, mem: &mut MEM) -> WORD {
-        // 6502 is little endian
-        let mut data = mem[self.pc as usize] as u16;
-        self.pc += 1;
-        *cycles -= 1;
-        data += (mem[self.pc as usize] as u16) << 8;
-        self.pc += 1;
-        *cycles -= 1;
+    fn set_flag(&mut self, mask: BYTE, value: bool) {
+        if value {
+            self.p |= mask;
+        } else {
+            self.p &= !mask;
+        }
+    }
+
+    /// Pack the status register for pushing to the stack. `brk`
+    /// distinguishes a software push (`BRK`/`PHP`, which sets the B flag)
+    /// from a hardware interrupt push (`IRQ`/`NMI`, which clears it).
+    fn to_byte(&self, brk: bool) -> BYTE {
+        let base = (self.p | flags::UNUSED) & !flags::BREAK;
+        if brk { base | flags::BREAK } else { base }
+    }
+
+    /// Restore the status register from a pulled byte (`PLP`/`RTI`). The
+    /// B bit in the pulled byte is discarded, same as real hardware.
+    fn set_flags_from(&mut self, byte: BYTE) {
+        self.p = (byte | flags::UNUSED) & !flags::BREAK;
+    }
+
+    // Cycle cost is charged by the caller (`step`'s `base_cycles`, or the
+    // fixed interrupt-sequence cost in `reset`/`irq`/`nmi`), not here.
+    fn push_byte(&mut self, value: BYTE, clock: &mut Clock, bus: &mut Bus) {
+        bus.write_byte(clock, 0x0100 + self.sp as WORD, value);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+
+    fn pull_byte(&mut self, clock: &mut Clock, bus: &mut Bus) -> BYTE {
+        self.sp = self.sp.wrapping_add(1);
+        bus.read_byte(clock, 0x0100 + self.sp as WORD)
+    }
+
+    fn push_word(&mut self, value: WORD, clock: &mut Clock, bus: &mut Bus) {
+        self.push_byte((value >> 8) as BYTE, clock, bus);
+        self.push_byte((value & 0xFF) as BYTE, clock, bus);
+    }
+
+    fn pull_word(&mut self, clock: &mut Clock, bus: &mut Bus) -> WORD {
+        let lo = self.pull_byte(clock, bus) as WORD;
+        let hi = self.pull_byte(clock, bus) as WORD;
+        lo | (hi << 8)
+    }
+
+    /// Push PC and P, set the I flag and vector through `vector`. Shared
+    /// by `BRK` and the external `irq`/`nmi` entry points; `brk` controls
+    /// whether the pushed status byte has the B flag set.
+    fn enter_interrupt(&mut self, vector: WORD, brk: bool, clock: &mut Clock, bus: &mut Bus) {
+        self.push_word(self.pc, clock, bus);
+        let status = self.to_byte(brk);
+        self.push_byte(status, clock, bus);
+        self.set_flag(flags::IRQ_DISABLE, true);
+        self.pc = self.read_vector(vector, clock, bus);
+    }
+
+    /// Service a maskable interrupt request, vectoring through
+    /// `0xFFFE/0xFFFF`. Ignored while the I flag is set; returns whether
+    /// it was actually serviced, so `step` knows whether to fall through
+    /// to the pending instruction instead.
+    fn irq(&mut self, clock: &mut Clock, bus: &mut Bus) -> bool {
+        if self.flag(flags::IRQ_DISABLE) {
+            return false;
+        }
+        self.enter_interrupt(0xFFFE, false, clock, bus);
+        clock.tick(INTERRUPT_SEQUENCE_CYCLES);
+        true
+    }
+
+    /// Service a non-maskable interrupt, vectoring through
+    /// `0xFFFA/0xFFFB`. Unlike `irq`, this cannot be masked.
+    fn nmi(&mut self, clock: &mut Clock, bus: &mut Bus) {
+        self.enter_interrupt(0xFFFA, false, clock, bus);
+        clock.tick(INTERRUPT_SEQUENCE_CYCLES);
+    }
+
+    /// Assert or deassert the IRQ line. A device (e.g. a timer) holds
+    /// this asserted until the CPU services it or it deasserts the line
+    /// itself; `step` polls it once per instruction boundary.
+    fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    /// Latch a pending NMI. Consumed by the next `step` regardless of
+    /// `IRQ_DISABLE`, then automatically cleared.
+    fn signal_nmi(&mut self) {
+        self.nmi_line = true;
+    }
+
+    fn fetch_byte(&mut self, clock: &mut Clock, bus: &mut Bus) -> BYTE {
+        let data = bus.read_byte(clock, self.pc);
+        self.pc = self.pc.wrapping_add(1);
         return data;
     }
 
-    fn read_byte(&mut self, cycles: &mut u32, addr: BYTE, mem: &mut MEM) -> BYTE {
-        let data = mem[addr as usize];
-        self.pc += 1;
-        *cycles -= 1;
+    fn fetch_word(&mut self, clock: &mut Clock, bus: &mut Bus) -> WORD {
+        // 6502 is little endian
+        let mut data = bus.read_byte(clock, self.pc) as u16;
+        self.pc = self.pc.wrapping_add(1);
+        data += (bus.read_byte(clock, self.pc) as u16) << 8;
+        self.pc = self.pc.wrapping_add(1);
         return data;
     }
 
     fn lda_set_status(&mut self) {
-        self.Z = (self.a == 0);
-        self.N = (self.a & 0b10000000) > 0;
-    }
-
-    fn exec(&mut self, cycles: &mut u32, mem: &mut MEM) {
-        while *cycles > 0 {
-            let instruction: BYTE = self.fetch_byte(cycles, mem);
-            match instruction {
-                0xA9 => { // const don't work INS_LDA_IM
-                    let value = self.fetch_byte(cycles, mem);
-                    self.a = value;
-                    self.lda_set_status()
+        self.set_nz(self.a);
+    }
+
+    /// Resolve an addressing mode into its effective operand, fetching
+    /// whatever bytes the mode needs from the instruction stream.
+    ///
+    /// Returns the operand plus whether resolving it crossed a page
+    /// boundary (relevant for `AbsoluteX`/`AbsoluteY`/`IndirectY`, which
+    /// cost an extra cycle on real hardware when the high byte changes).
+    fn resolve_operand(&mut self, mode: AddrMode, clock: &mut Clock, bus: &mut Bus) -> (Operand, bool) {
+        match mode {
+            AddrMode::Implied => (Operand::Implied, false),
+            AddrMode::Accumulator => (Operand::Accumulator, false),
+            AddrMode::Immediate => {
+                let value = self.fetch_byte(clock, bus);
+                (Operand::Immediate(value), false)
+            }
+            AddrMode::ZeroPage => {
+                let addr = self.fetch_byte(clock, bus) as WORD;
+                (Operand::Address(addr), false)
+            }
+            AddrMode::ZeroPageX => {
+                let base = self.fetch_byte(clock, bus);
+                let addr = base.wrapping_add(self.x) as WORD;
+                (Operand::Address(addr), false)
+            }
+            AddrMode::ZeroPageY => {
+                let base = self.fetch_byte(clock, bus);
+                let addr = base.wrapping_add(self.y) as WORD;
+                (Operand::Address(addr), false)
+            }
+            AddrMode::Absolute => {
+                let addr = self.fetch_word(clock, bus);
+                (Operand::Address(addr), false)
+            }
+            AddrMode::AbsoluteX => {
+                let base = self.fetch_word(clock, bus);
+                let addr = base.wrapping_add(self.x as WORD);
+                let crossed = (base & 0xFF00) != (addr & 0xFF00);
+                (Operand::Address(addr), crossed)
+            }
+            AddrMode::AbsoluteY => {
+                let base = self.fetch_word(clock, bus);
+                let addr = base.wrapping_add(self.y as WORD);
+                let crossed = (base & 0xFF00) != (addr & 0xFF00);
+                (Operand::Address(addr), crossed)
+            }
+            AddrMode::Indirect => {
+                let ptr = self.fetch_word(clock, bus);
+                let addr = self.read_word_bug(ptr, clock, bus);
+                (Operand::Address(addr), false)
+            }
+            AddrMode::IndirectX => {
+                let base = self.fetch_byte(clock, bus);
+                let ptr = base.wrapping_add(self.x);
+                let addr = self.read_word_zero_page(ptr, clock, bus);
+                (Operand::Address(addr), false)
+            }
+            AddrMode::IndirectY => {
+                let ptr = self.fetch_byte(clock, bus);
+                let base = self.read_word_zero_page(ptr, clock, bus);
+                let addr = base.wrapping_add(self.y as WORD);
+                let crossed = (base & 0xFF00) != (addr & 0xFF00);
+                (Operand::Address(addr), crossed)
+            }
+            AddrMode::Relative => {
+                let offset = self.fetch_byte(clock, bus) as i8;
+                let pc_after_operand = self.pc;
+                let addr = (pc_after_operand as i32 + offset as i32) as WORD;
+                let crossed = (pc_after_operand & 0xFF00) != (addr & 0xFF00);
+                (Operand::Address(addr), crossed)
+            }
+        }
+    }
+
+    /// Read a little-endian word from two consecutive zero-page bytes,
+    /// wrapping the high byte back to the start of the page as real 6502
+    /// hardware does (used by the `(indirect,X)`/`(indirect),Y` modes).
+    fn read_word_zero_page(&mut self, addr: BYTE, clock: &mut Clock, bus: &mut Bus) -> WORD {
+        let lo = bus.read_byte(clock, addr as WORD) as WORD;
+        let hi = bus.read_byte(clock, addr.wrapping_add(1) as WORD) as WORD;
+        lo | (hi << 8)
+    }
+
+    /// Read a little-endian word for the `Indirect` (JMP) addressing
+    /// mode, reproducing the famous 6502 page-wrap bug where the high
+    /// byte is fetched from the start of the *same* page rather than the
+    /// next one when the pointer's low byte is `0xFF`.
+    fn read_word_bug(&mut self, ptr: WORD, clock: &mut Clock, bus: &mut Bus) -> WORD {
+        let lo = bus.read_byte(clock, ptr) as WORD;
+        let hi_addr = if ptr & 0x00FF == 0x00FF {
+            ptr & 0xFF00
+        } else {
+            ptr + 1
+        };
+        let hi = bus.read_byte(clock, hi_addr) as WORD;
+        lo | (hi << 8)
+    }
+
+    fn load_operand(&mut self, operand: Operand, clock: &mut Clock, bus: &mut Bus) -> BYTE {
+        match operand {
+            Operand::Immediate(value) => value,
+            Operand::Accumulator => self.a,
+            Operand::Address(addr) => bus.read_byte(clock, addr),
+            Operand::Implied => unreachable!("implied addressing has no operand value"),
+        }
+    }
+
+    fn store_operand(&mut self, operand: Operand, value: BYTE, clock: &mut Clock, bus: &mut Bus) {
+        match operand {
+            Operand::Accumulator => self.a = value,
+            Operand::Address(addr) => bus.write_byte(clock, addr, value),
+            Operand::Immediate(_) | Operand::Implied => {
+                unreachable!("cannot store to an immediate or implied operand")
+            }
+        }
+    }
+
+    fn set_nz(&mut self, value: BYTE) {
+        self.set_flag(flags::ZERO, value == 0);
+        self.set_flag(flags::NEGATIVE, (value & 0b1000_0000) > 0);
+    }
+
+    /// Binary `ADC`: `A + value + carry`, with the usual carry/overflow
+    /// rules.
+    fn adc_binary(&mut self, value: BYTE) {
+        let carry_in = self.flag(flags::CARRY) as u16;
+        let result = self.a as u16 + value as u16 + carry_in;
+        let overflow = (self.a ^ value) & 0x80 == 0 && (self.a as u16 ^ result) & 0x80 != 0;
+        self.set_flag(flags::OVERFLOW, overflow);
+        self.set_flag(flags::CARRY, result > 0xFF);
+        self.a = result as BYTE;
+        self.lda_set_status();
+    }
+
+    /// BCD `ADC`, following the nibble-correction algorithm from
+    /// 6502.org's decimal mode reference: add the low nibbles (plus
+    /// carry), correct if it overflowed 9, then do the same for the high
+    /// nibbles. `V` is left untouched — real NMOS hardware's `V` (and,
+    /// depending on the chip, `N`/`Z`) are undefined in decimal mode, and
+    /// nothing in this codebase relies on them there.
+    fn adc_decimal(&mut self, value: BYTE) {
+        let carry_in = self.flag(flags::CARRY) as u16;
+        let mut lo = (self.a as u16 & 0x0F) + (value as u16 & 0x0F) + carry_in;
+        if lo > 0x09 {
+            lo = ((lo + 0x06) & 0x0F) + 0x10;
+        }
+        let mut sum = (self.a as u16 & 0xF0) + (value as u16 & 0xF0) + lo;
+        if sum > 0x9F {
+            sum += 0x60;
+        }
+        self.set_flag(flags::CARRY, sum > 0xFF);
+        self.a = sum as BYTE;
+        self.lda_set_status();
+    }
+
+    /// Binary `SBC`: `A + !value + carry`, i.e. `ADC` with the operand's
+    /// one's complement (`carry` doubles as "no borrow").
+    fn sbc_binary(&mut self, value: BYTE) {
+        let carry_in = self.flag(flags::CARRY) as u16;
+        let result = self.a as u16 + (!value) as u16 + carry_in;
+        let overflow = (self.a ^ !value) & 0x80 == 0 && (self.a as u16 ^ result) & 0x80 != 0;
+        self.set_flag(flags::OVERFLOW, overflow);
+        self.set_flag(flags::CARRY, result > 0xFF);
+        self.a = result as BYTE;
+        self.lda_set_status();
+    }
+
+    /// BCD `SBC`, the subtractive counterpart of `adc_decimal`: subtract
+    /// the low nibbles (less a borrow), correct on underflow, then the
+    /// same for the high nibbles. Same caveat on `V` as `adc_decimal`.
+    fn sbc_decimal(&mut self, value: BYTE) {
+        let carry_in = self.flag(flags::CARRY) as i16;
+        let a = self.a as i16;
+        let b = value as i16;
+        let mut lo = (a & 0x0F) - (b & 0x0F) + carry_in - 1;
+        if lo < 0 {
+            lo = ((lo - 0x06) & 0x0F) - 0x10;
+        }
+        let mut diff = (a & 0xF0) - (b & 0xF0) + lo;
+        if diff < 0 {
+            diff -= 0x60;
+        }
+        self.set_flag(flags::CARRY, diff >= 0);
+        self.a = (diff & 0xFF) as BYTE;
+        self.lda_set_status();
+    }
+
+    fn branch(&mut self, operand: Operand, clock: &mut Clock, taken: bool, page_crossed: bool) {
+        if let Operand::Address(addr) = operand {
+            if taken {
+                clock.tick(1);
+                if page_crossed {
+                    clock.tick(1);
+                }
+                self.pc = addr;
+            }
+        }
+    }
+
+    /// Execute exactly one instruction and return how much simulated time
+    /// it cost. Shared by `exec`'s loop and the fuzz harness, which drives
+    /// `step` directly instead of handing it a deadline.
+    ///
+    /// Before fetching, polls the interrupt lines: a pending NMI is always
+    /// serviced and clears the latch; a pending IRQ is serviced unless
+    /// `IRQ_DISABLE` is set, in which case it's left asserted and this call
+    /// executes the next instruction normally instead. Either way, at most
+    /// one of {service an interrupt, execute an instruction} happens per
+    /// `step` call.
+    ///
+    /// Timing is driven by `decode`'s `base_cycles`: every sub-step this
+    /// method calls (fetch, push/pull, load/store) is cycle-agnostic, and
+    /// `base_cycles` is ticked once at the end for the whole instruction.
+    /// Only the conditional extras that `base_cycles` doesn't cover —
+    /// a page-crossing penalty on indexed loads, and the taken/page-cross
+    /// penalties on branches — are ticked separately, inline below.
+    fn step(&mut self, clock: &mut Clock, bus: &mut Bus) -> clock::ClockDuration {
+        let start = clock.now();
+        if self.nmi_line {
+            self.nmi_line = false;
+            self.nmi(clock, bus);
+            return clock.now() - start;
+        }
+        if self.irq_line && self.irq(clock, bus) {
+            return clock.now() - start;
+        }
+        if self.trace {
+            let (text, _len) = disasm::disassemble(bus, self.pc);
+            println!(
+                "{:#06X}  {:<16} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+                self.pc, text, self.a, self.x, self.y, self.p, self.sp
+            );
+        }
+        let opcode = self.fetch_byte(clock, bus);
+        let (op, mode, base_cycles) = decode(opcode);
+        let (operand, page_crossed) = self.resolve_operand(mode, clock, bus);
+
+        match op {
+            Op::Lda => {
+                self.a = self.load_operand(operand, clock, bus);
+                self.lda_set_status();
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Ldx => {
+                self.x = self.load_operand(operand, clock, bus);
+                self.set_nz(self.x);
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Ldy => {
+                self.y = self.load_operand(operand, clock, bus);
+                self.set_nz(self.y);
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Sta => self.store_operand(operand, self.a, clock, bus),
+            Op::Stx => self.store_operand(operand, self.x, clock, bus),
+            Op::Sty => self.store_operand(operand, self.y, clock, bus),
+            Op::And => {
+                let value = self.load_operand(operand, clock, bus);
+                self.a &= value;
+                self.lda_set_status();
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Ora => {
+                let value = self.load_operand(operand, clock, bus);
+                self.a |= value;
+                self.lda_set_status();
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Eor => {
+                let value = self.load_operand(operand, clock, bus);
+                self.a ^= value;
+                self.lda_set_status();
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Adc => {
+                let value = self.load_operand(operand, clock, bus);
+                if self.flag(flags::DECIMAL) {
+                    self.adc_decimal(value);
+                } else {
+                    self.adc_binary(value);
                 }
-                0xA5 => { // const don't work INS_LDA_ZP
-                    let zero_page_addr = self.fetch_byte(cycles, mem);
-                    self.a = self.read_byte(cycles, zero_page_addr, mem);
-                    self.lda_set_status()
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Sbc => {
+                let value = self.load_operand(operand, clock, bus);
+                if self.flag(flags::DECIMAL) {
+                    self.sbc_decimal(value);
+                } else {
+                    self.sbc_binary(value);
                 }
-                0xB5 => { // const don't work INS_LDA_ZP_X
-                    let zero_page_addr_X = self.fetch_byte(cycles, mem) + self.x;
-                    *cycles -= 1; // for the zero page addr + X register calulation
-                    self.a = self.read_byte(cycles, zero_page_addr_X, mem);
-                    self.lda_set_status()
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Bit => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::ZERO, (self.a & value) == 0);
+                self.set_flag(flags::NEGATIVE, (value & 0b1000_0000) > 0);
+                self.set_flag(flags::OVERFLOW, (value & 0b0100_0000) > 0);
+            }
+            Op::Asl => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::CARRY, (value & 0b1000_0000) > 0);
+                let result = value << 1;
+                self.set_nz(result);
+                self.store_operand(operand, result, clock, bus);
+            }
+            Op::Lsr => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::CARRY, (value & 0b0000_0001) > 0);
+                let result = value >> 1;
+                self.set_nz(result);
+                self.store_operand(operand, result, clock, bus);
+            }
+            Op::Rol => {
+                let value = self.load_operand(operand, clock, bus);
+                let carry_in = self.flag(flags::CARRY) as BYTE;
+                self.set_flag(flags::CARRY, (value & 0b1000_0000) > 0);
+                let result = (value << 1) | carry_in;
+                self.set_nz(result);
+                self.store_operand(operand, result, clock, bus);
+            }
+            Op::Ror => {
+                let value = self.load_operand(operand, clock, bus);
+                let carry_in = self.flag(flags::CARRY) as BYTE;
+                self.set_flag(flags::CARRY, (value & 0b0000_0001) > 0);
+                let result = (value >> 1) | (carry_in << 7);
+                self.set_nz(result);
+                self.store_operand(operand, result, clock, bus);
+            }
+            Op::Cmp => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::CARRY, self.a >= value);
+                self.set_nz(self.a.wrapping_sub(value));
+                if page_crossed { clock.tick(1); }
+            }
+            Op::Cpx => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::CARRY, self.x >= value);
+                self.set_nz(self.x.wrapping_sub(value));
+            }
+            Op::Cpy => {
+                let value = self.load_operand(operand, clock, bus);
+                self.set_flag(flags::CARRY, self.y >= value);
+                self.set_nz(self.y.wrapping_sub(value));
+            }
+            Op::Inc => {
+                let value = self.load_operand(operand, clock, bus).wrapping_add(1);
+                self.set_nz(value);
+                self.store_operand(operand, value, clock, bus);
+            }
+            Op::Inx => { self.x = self.x.wrapping_add(1); self.set_nz(self.x); }
+            Op::Iny => { self.y = self.y.wrapping_add(1); self.set_nz(self.y); }
+            Op::Dec => {
+                let value = self.load_operand(operand, clock, bus).wrapping_sub(1);
+                self.set_nz(value);
+                self.store_operand(operand, value, clock, bus);
+            }
+            Op::Dex => { self.x = self.x.wrapping_sub(1); self.set_nz(self.x); }
+            Op::Dey => { self.y = self.y.wrapping_sub(1); self.set_nz(self.y); }
+            Op::Tax => { self.x = self.a; self.set_nz(self.x); }
+            Op::Txa => { self.a = self.x; self.set_nz(self.a); }
+            Op::Tay => { self.y = self.a; self.set_nz(self.y); }
+            Op::Tya => { self.a = self.y; self.set_nz(self.a); }
+            Op::Tsx => { self.x = self.sp; self.set_nz(self.x); }
+            Op::Txs => { self.sp = self.x; }
+            Op::Pha => self.push_byte(self.a, clock, bus),
+            Op::Pla => {
+                self.a = self.pull_byte(clock, bus);
+                self.set_nz(self.a);
+            }
+            Op::Php => {
+                let status = self.to_byte(true);
+                self.push_byte(status, clock, bus);
+            }
+            Op::Plp => {
+                let status = self.pull_byte(clock, bus);
+                self.set_flags_from(status);
+            }
+            Op::Jmp => {
+                if let Operand::Address(addr) = operand {
+                    self.pc = addr;
                 }
-                0x20 => { // const don't work INS_JSR
-                    let subAddr = self.fetch_word(cycles, mem);
-                    mem.write_word(self.pc - 1, self.sp as u32, cycles);
-                    self.pc = subAddr;
-                    *cycles -= 1;
+            }
+            Op::Jsr => {
+                if let Operand::Address(addr) = operand {
+                    self.push_word(self.pc.wrapping_sub(1), clock, bus);
+                    self.pc = addr;
                 }
-                _=> break
+            }
+            Op::Rts => {
+                self.pc = self.pull_word(clock, bus).wrapping_add(1);
+            }
+            Op::Brk => {
+                self.fetch_byte(clock, bus); // BRK's signature byte, discarded
+                self.enter_interrupt(0xFFFE, true, clock, bus);
+            }
+            Op::Rti => {
+                let status = self.pull_byte(clock, bus);
+                self.set_flags_from(status);
+                self.pc = self.pull_word(clock, bus);
+            }
+            Op::Bcc => self.branch(operand, clock, !self.flag(flags::CARRY), page_crossed),
+            Op::Bcs => self.branch(operand, clock, self.flag(flags::CARRY), page_crossed),
+            Op::Beq => self.branch(operand, clock, self.flag(flags::ZERO), page_crossed),
+            Op::Bne => self.branch(operand, clock, !self.flag(flags::ZERO), page_crossed),
+            Op::Bmi => self.branch(operand, clock, self.flag(flags::NEGATIVE), page_crossed),
+            Op::Bpl => self.branch(operand, clock, !self.flag(flags::NEGATIVE), page_crossed),
+            Op::Bvc => self.branch(operand, clock, !self.flag(flags::OVERFLOW), page_crossed),
+            Op::Bvs => self.branch(operand, clock, self.flag(flags::OVERFLOW), page_crossed),
+            Op::Clc => self.set_flag(flags::CARRY, false),
+            Op::Sec => self.set_flag(flags::CARRY, true),
+            Op::Cld => self.set_flag(flags::DECIMAL, false),
+            Op::Sed => self.set_flag(flags::DECIMAL, true),
+            Op::Cli => self.set_flag(flags::IRQ_DISABLE, false),
+            Op::Sei => self.set_flag(flags::IRQ_DISABLE, true),
+            Op::Clv => self.set_flag(flags::OVERFLOW, false),
+            Op::Nop => {}
+            Op::Unimplemented(raw) => {
+                panic!("unimplemented opcode {:#04X} at PC {:#06X}", raw, self.pc.wrapping_sub(1));
             }
         }
+
+        clock.tick(base_cycles);
+        clock.now() - start
+    }
+
+    /// The base cost of whatever `step` would do next: the fixed interrupt
+    /// sequence cost if a pending NMI or unmasked IRQ would be serviced, or
+    /// the `decode`d `base_cycles` of the opcode sitting at `pc` otherwise.
+    /// Used by `exec` to look before it leaps; doesn't (and can't, without
+    /// actually executing) account for page-cross or branch-taken extras,
+    /// since those depend on runtime state `step` hasn't resolved yet.
+    fn next_step_base_cycles(&self, bus: &mut Bus) -> u32 {
+        if self.nmi_line || (self.irq_line && !self.flag(flags::IRQ_DISABLE)) {
+            return INTERRUPT_SEQUENCE_CYCLES;
+        }
+        let (_op, _mode, base_cycles) = decode(bus.peek_byte(self.pc));
+        base_cycles
+    }
+
+    /// Run instructions until `clock` would reach `deadline`, one `step`
+    /// at a time, stopping before a `step` that would overshoot it.
+    ///
+    /// The pre-check only covers the next step's base cost — a page-cross
+    /// or taken-branch penalty is only known once `step` actually resolves
+    /// the operand, so a step right at the edge of `deadline` can still
+    /// overshoot by that small amount.
+    fn exec(&mut self, clock: &mut Clock, deadline: clock::ClockDuration, bus: &mut Bus) {
+        while clock.now() < deadline {
+            let cost = clock.cycle_duration() * self.next_step_base_cycles(bus);
+            if clock.now() + cost > deadline {
+                break;
+            }
+            self.step(clock, bus);
+        }
     }
 }
 
 fn main() {
-    let mut mem = MEM{data:[0; 1024 * 64]};
-    let mut cpu = CPU { pc: 0, sp: 0, a: 0, x: 0, y: 0, C: 0, Z: false, I: 0, D: 0, B: 0, V: 0, N: false };
-    cpu.reset( &mut mem);
-    print!("CPU: {:?}", cpu);
+    let mut clock = Clock::with_frequency_hz(1_023_000.0);
+    let mut bus = Bus::new();
+    bus.map(0x0000, 0xFFFF, Box::new(Ram::new(1024 * 64)));
+
+    let mut cpu = CPU {
+        pc: 0, sp: 0, a: 0, x: 0, y: 0, p: 0, trace: true,
+        irq_line: false, nmi_line: false,
+    };
+    // reset vector points at the start of our inline program
+    bus.write_byte(&mut clock, 0xFFFC, 0x00);
+    bus.write_byte(&mut clock, 0xFFFD, 0x80);
+    cpu.reset(&mut clock, &mut bus);
     // start inline program
-    mem[0xFFFC] = CPU::INS_LDA_ZP;
-    mem[0xFFFD] = 0x42;
-    mem[0x42] = 0x10;
+    bus.write_byte(&mut clock, 0x8000, CPU::INS_LDA_ZP);
+    bus.write_byte(&mut clock, 0x8001, 0x42);
+    bus.write_byte(&mut clock, 0x42, 0x10);
     // end inline program
-    cpu.exec( &mut 3, &mut mem);
-    print!("MEM: {:?}", mem);
+    let deadline = clock.now() + clock.cycle_duration() * 3;
+    cpu.exec(&mut clock, deadline, &mut bus);
     print!("CPU: {:?}", cpu);
+
+    // demonstrate a device requesting service on the IRQ line: the next
+    // `step` call polls it and vectors through 0xFFFE/0xFFFF on its own
+    bus.write_byte(&mut clock, 0xFFFE, 0x00);
+    bus.write_byte(&mut clock, 0xFFFF, 0x90);
+    cpu.set_flag(flags::IRQ_DISABLE, false);
+    cpu.set_irq_line(true);
+    cpu.step(&mut clock, &mut bus);
+    print!(", after IRQ: {:?}", cpu);
+    cpu.set_irq_line(false);
+
+    // demonstrate an edge-sensitive NMI: `signal_nmi` latches it, and the
+    // next `step` services it unconditionally, then clears the latch
+    bus.write_byte(&mut clock, 0xFFFA, 0x00);
+    bus.write_byte(&mut clock, 0xFFFB, 0xA0);
+    cpu.signal_nmi();
+    cpu.step(&mut clock, &mut bus);
+    print!(", after NMI: {:?}", cpu);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tiny xorshift generator so the fuzz harness below doesn't need an
+    /// external crate. Not cryptographic; just deterministic per seed and
+    /// varied enough to exercise most of the opcode space.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_byte(&mut self) -> BYTE {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 & 0xFF) as BYTE
+        }
+    }
+
+    fn new_cpu_and_bus() -> (CPU, Bus, Clock) {
+        let clock = Clock::with_frequency_hz(1_023_000.0);
+        let mut bus = Bus::new();
+        bus.map(0x0000, 0xFFFF, Box::new(Ram::new(1024 * 64)));
+        let cpu = CPU {
+            pc: 0, sp: 0, a: 0, x: 0, y: 0, p: 0, trace: false,
+            irq_line: false, nmi_line: false,
+        };
+        (cpu, bus, clock)
+    }
+
+    /// The most extra cycles (on top of `decode`'s `base_cycles`) a given
+    /// `(op, mode)` is allowed to charge: +2 for a taken branch that also
+    /// crosses a page, +1 for an indexed/indirect-indexed read that
+    /// crosses a page, 0 otherwise.
+    fn max_extra_cycles(op: Op, mode: AddrMode) -> u32 {
+        use AddrMode::*;
+        use Op::*;
+        match op {
+            Bcc | Bcs | Beq | Bmi | Bne | Bpl | Bvc | Bvs => 2,
+            Lda | Ldx | Ldy | And | Ora | Eor | Adc | Sbc | Cmp => match mode {
+                AbsoluteX | AbsoluteY | IndirectY => 1,
+                _ => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// How much a single execution of `op` must move `sp`, independent of
+    /// any random operand: push ops move it by -1 (-3 for `Brk`, which
+    /// pushes PC and status), pull ops by +1 (+3 for `Rti`), `Jsr`/`Rts`
+    /// by -2/+2, everything else leaves it untouched. `Txs` is the one
+    /// exception: it loads `sp` from `x`, so the harness checks it apart.
+    fn stack_depth_delta(op: Op) -> i32 {
+        use Op::*;
+        match op {
+            Pha | Php => -1,
+            Pla | Plp => 1,
+            Jsr => -2,
+            Rts => 2,
+            Brk => -3,
+            Rti => 3,
+            _ => 0,
+        }
+    }
+
+    /// Fill `0x8000..=0x80FF` with `seed`-derived random bytes, point the
+    /// reset vector there, and drive `step` for up to `max_instructions`,
+    /// checking the invariants a real core must hold: every step charges
+    /// only `base_cycles` plus the penalties that opcode is actually
+    /// allowed to charge, `sp` only ever moves by the pushed/pulled byte
+    /// count the decoded opcode implies, and any opcode this decode table
+    /// doesn't know is explicitly flagged rather than silently skipped.
+    fn run_fuzzed_program(seed: u64, max_instructions: usize) {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        let mut rng = Rng(seed);
+
+        bus.write_byte(&mut clock, 0xFFFC, 0x00);
+        bus.write_byte(&mut clock, 0xFFFD, 0x80);
+        for addr in 0x8000..=0x80FFu16 {
+            bus.write_byte(&mut clock, addr, rng.next_byte());
+        }
+        cpu.reset(&mut clock, &mut bus);
+
+        for _ in 0..max_instructions {
+            let (op, mode, base_cycles) = decode(bus.peek_byte(cpu.pc));
+            let sp_before = cpu.sp;
+            let x_before = cpu.x;
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                cpu.step(&mut clock, &mut bus)
+            }));
+            match outcome {
+                Ok(cost) => {
+                    let cycles_charged = (cost.0 / clock.cycle_duration().0) as u32;
+                    let max_extra = max_extra_cycles(op, mode);
+                    assert!(
+                        (base_cycles..=base_cycles + max_extra).contains(&cycles_charged),
+                        "{op:?} ({mode:?}) charged {cycles_charged} cycles, expected {base_cycles}..={}",
+                        base_cycles + max_extra
+                    );
+
+                    let delta = stack_depth_delta(op);
+                    let expected_sp = if op == Op::Txs {
+                        x_before
+                    } else {
+                        (sp_before as i32 + delta).rem_euclid(256) as BYTE
+                    };
+                    assert_eq!(
+                        cpu.sp, expected_sp,
+                        "{op:?} moved sp from {sp_before:#04X} to {:#04X}, expected {expected_sp:#04X}",
+                        cpu.sp
+                    );
+                }
+                Err(payload) => {
+                    let message = payload
+                        .downcast_ref::<String>()
+                        .map(String::as_str)
+                        .or_else(|| payload.downcast_ref::<&str>().copied())
+                        .unwrap_or("");
+                    assert!(
+                        message.contains("unimplemented opcode"),
+                        "step panicked for a reason other than an explicitly flagged \
+                         unimplemented opcode: {message}"
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fuzzed_programs_never_violate_core_invariants() {
+        for seed in 0..32u64 {
+            run_fuzzed_program(seed ^ 0x9E3779B97F4A7C15, 256);
+        }
+    }
+
+    #[test]
+    fn status_byte_round_trips_through_push_and_pull() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        cpu.sp = 0xFF;
+
+        for flags in [
+            0x00,
+            0xFF,
+            flags::NEGATIVE | flags::CARRY,
+            flags::ZERO | flags::DECIMAL,
+        ] {
+            cpu.p = flags;
+            let pushed = cpu.to_byte(false);
+            cpu.push_byte(pushed, &mut clock, &mut bus);
+            let pulled = cpu.pull_byte(&mut clock, &mut bus);
+            cpu.set_flags_from(pulled);
+            assert_eq!(
+                cpu.to_byte(false),
+                pushed,
+                "status byte did not round-trip for {flags:#04X}"
+            );
+        }
+    }
+
+    #[test]
+    fn brk_and_rti_round_trip_pc_and_status() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        bus.write_byte(&mut clock, 0xFFFE, 0x00);
+        bus.write_byte(&mut clock, 0xFFFF, 0x90); // BRK vectors to $9000
+        bus.write_byte(&mut clock, 0x9000, 0x40); // RTI sits at the handler
+        cpu.pc = 0x1234;
+        cpu.sp = 0xFF;
+        cpu.p = flags::UNUSED | flags::NEGATIVE;
+        let original_p = cpu.p;
+        bus.write_byte(&mut clock, 0x1234, 0x00); // BRK
+
+        cpu.step(&mut clock, &mut bus); // BRK
+        assert_eq!(cpu.pc, 0x9000, "BRK did not vector through 0xFFFE/0xFFFF");
+        assert!(cpu.flag(flags::IRQ_DISABLE), "BRK must set the I flag");
+        assert_eq!(cpu.sp, 0xFC, "BRK must push PC and status (3 bytes)");
+
+        cpu.step(&mut clock, &mut bus); // RTI
+        assert_eq!(cpu.pc, 0x1236, "RTI did not restore the PC pushed by BRK");
+        assert_eq!(cpu.sp, 0xFF, "RTI must pull PC and status back off the stack");
+        assert_eq!(cpu.p, original_p, "RTI did not restore the pre-interrupt status byte");
+    }
+
+    #[test]
+    fn exec_stops_before_a_step_that_would_pass_the_deadline() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        cpu.pc = 0x8000;
+        bus.write_byte(&mut clock, 0x8000, 0xA9); // LDA #$42, base_cycles = 2
+        bus.write_byte(&mut clock, 0x8001, 0x42);
+
+        let deadline = clock.now() + clock.cycle_duration(); // less than the 2-cycle cost
+        cpu.exec(&mut clock, deadline, &mut bus);
+
+        assert_eq!(cpu.pc, 0x8000, "exec must not execute a step that would overshoot the deadline");
+        assert_eq!(clock.now().0, 0, "exec must not advance the clock if nothing was stepped");
+    }
+
+    #[test]
+    fn taken_branch_charges_an_extra_cycle_only_when_it_crosses_a_page() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        cpu.set_flag(flags::ZERO, false); // BNE taken: Z clear
+
+        // $80FD: BNE +2 -> lands on $8101, crossing from page $80 to $81.
+        cpu.pc = 0x80FD;
+        bus.write_byte(&mut clock, 0x80FD, 0xD0);
+        bus.write_byte(&mut clock, 0x80FE, 0x02);
+        let crossing_cost = cpu.step(&mut clock, &mut bus);
+        assert_eq!(cpu.pc, 0x8101, "branch did not land on the expected target");
+        assert_eq!(
+            crossing_cost,
+            clock.cycle_duration() * 4,
+            "a taken branch that crosses a page should cost base (2) + taken (1) + page-cross (1)"
+        );
+
+        // $9000: BNE +2 -> lands on $9004, staying on the same page.
+        cpu.pc = 0x9000;
+        bus.write_byte(&mut clock, 0x9000, 0xD0);
+        bus.write_byte(&mut clock, 0x9001, 0x02);
+        let same_page_cost = cpu.step(&mut clock, &mut bus);
+        assert_eq!(cpu.pc, 0x9004, "branch did not land on the expected target");
+        assert_eq!(
+            same_page_cost,
+            clock.cycle_duration() * 3,
+            "a taken branch on the same page should cost base (2) + taken (1), no page-cross penalty"
+        );
+    }
+
+    #[test]
+    fn irq_vectors_through_fffe_and_pushes_pc_and_status() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        bus.write_byte(&mut clock, 0xFFFE, 0x00);
+        bus.write_byte(&mut clock, 0xFFFF, 0x90);
+        cpu.pc = 0x1234;
+        cpu.sp = 0xFF;
+        cpu.p = flags::UNUSED;
+
+        cpu.irq(&mut clock, &mut bus);
+
+        assert_eq!(cpu.pc, 0x9000, "irq did not vector through 0xFFFE/0xFFFF");
+        assert!(cpu.flag(flags::IRQ_DISABLE), "irq must set the I flag");
+        assert_eq!(cpu.sp, 0xFC, "irq must push PC and P (3 bytes)");
+    }
+
+    #[test]
+    fn irq_is_ignored_while_irq_disable_is_set() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        cpu.pc = 0x1234;
+        cpu.sp = 0xFF;
+        cpu.set_flag(flags::IRQ_DISABLE, true);
+
+        cpu.irq(&mut clock, &mut bus);
+
+        assert_eq!(cpu.pc, 0x1234, "a masked irq must not vector");
+        assert_eq!(cpu.sp, 0xFF, "a masked irq must not touch the stack");
+    }
+
+    #[test]
+    fn nmi_vectors_through_fffa_even_when_irq_disable_is_set() {
+        let (mut cpu, mut bus, mut clock) = new_cpu_and_bus();
+        bus.write_byte(&mut clock, 0xFFFA, 0x00);
+        bus.write_byte(&mut clock, 0xFFFB, 0xA0);
+        cpu.pc = 0x1234;
+        cpu.sp = 0xFF;
+        cpu.set_flag(flags::IRQ_DISABLE, true);
+
+        cpu.nmi(&mut clock, &mut bus);
+
+        assert_eq!(cpu.pc, 0xA000, "nmi did not vector through 0xFFFA/0xFFFB");
+    }
+
+    #[test]
+    fn adc_and_sbc_use_bcd_in_decimal_mode() {
+        let (mut cpu, _bus, _clock) = new_cpu_and_bus();
+        cpu.set_flag(flags::DECIMAL, true);
+
+        cpu.a = 0x58;
+        cpu.set_flag(flags::CARRY, false);
+        cpu.adc_decimal(0x46);
+        assert_eq!(cpu.a, 0x04, "58 + 46 in BCD should be 104, wrapping to 04");
+        assert!(cpu.flag(flags::CARRY), "58 + 46 in BCD should carry out of the hundreds digit");
+
+        cpu.a = 0x12;
+        cpu.set_flag(flags::CARRY, true); // carry set means "no borrow"
+        cpu.sbc_decimal(0x21);
+        assert_eq!(cpu.a, 0x91, "12 - 21 in BCD should borrow to 91");
+        assert!(!cpu.flag(flags::CARRY), "a BCD borrow should clear carry");
+
+        // Decimal mode should leave the binary path alone.
+        cpu.set_flag(flags::DECIMAL, false);
+        cpu.a = 0x58;
+        cpu.set_flag(flags::CARRY, false);
+        cpu.adc_binary(0x46);
+        assert_eq!(cpu.a, 0x9E, "binary ADC must not apply BCD correction");
+    }
 }